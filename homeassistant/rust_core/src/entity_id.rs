@@ -151,6 +151,58 @@ pub fn split_entity_id_fast(entity_id: &str) -> Option<(&str, &str)> {
     Some((domain, object_id))
 }
 
+/// Slugify an arbitrary string into a valid object_id.
+///
+/// Produces an object_id that satisfies [`is_valid_object_id`] by operating
+/// directly on the input bytes:
+/// - ASCII uppercase letters are mapped to lowercase
+/// - any run of characters outside `[a-z0-9]` collapses to a single `_`
+/// - leading and trailing underscores are dropped
+///
+/// Returns `None` when nothing valid remains (e.g. the input was empty or
+/// contained no `[a-z0-9]` characters). The result is built in a single
+/// output allocation.
+///
+/// # Performance
+/// Works on bytes with no intermediate allocations, so it is suitable for
+/// building entity IDs on the hot path with a guarantee the result
+/// round-trips through the validator.
+///
+/// # Examples
+/// ```
+/// use homeassistant_rust_core::entity_id::slugify_object_id;
+///
+/// assert_eq!(slugify_object_id("Living Room").as_deref(), Some("living_room"));
+/// assert_eq!(slugify_object_id("  Hall -- Light! ").as_deref(), Some("hall_light"));
+/// assert_eq!(slugify_object_id("***"), None);
+/// ```
+pub fn slugify_object_id(name: &str) -> Option<String> {
+    let bytes = name.as_bytes();
+    let mut out = String::with_capacity(bytes.len());
+    // Whether a separator is pending; emitted lazily so leading and trailing
+    // runs never produce an underscore.
+    let mut pending_separator = false;
+
+    for &byte in bytes {
+        let lower = byte.to_ascii_lowercase();
+        if lower.is_ascii_lowercase() || lower.is_ascii_digit() {
+            if pending_separator && !out.is_empty() {
+                out.push('_');
+            }
+            pending_separator = false;
+            out.push(lower as char);
+        } else {
+            pending_separator = true;
+        }
+    }
+
+    if out.is_empty() {
+        None
+    } else {
+        Some(out)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -228,6 +280,36 @@ mod tests {
         assert_eq!(split_entity_id_fast(".living"), None);
     }
 
+    #[test]
+    fn test_slugify_object_id() {
+        assert_eq!(slugify_object_id("Living Room").as_deref(), Some("living_room"));
+        assert_eq!(slugify_object_id("temp1").as_deref(), Some("temp1"));
+        assert_eq!(
+            slugify_object_id("  Hall -- Light! ").as_deref(),
+            Some("hall_light")
+        );
+        assert_eq!(slugify_object_id("Café").as_deref(), Some("caf")); // accents dropped
+        assert_eq!(slugify_object_id("__already__").as_deref(), Some("already"));
+
+        // Nothing valid remains.
+        assert_eq!(slugify_object_id(""), None);
+        assert_eq!(slugify_object_id("***"), None);
+        assert_eq!(slugify_object_id("   "), None);
+    }
+
+    #[test]
+    fn test_slugify_round_trips_through_validator() {
+        for name in ["Living Room", "Hall -- Light!", "Node 2", "A.B.C"] {
+            let object_id = slugify_object_id(name).expect("should produce a slug");
+            assert!(
+                is_valid_object_id(&object_id),
+                "slug {:?} from {:?} is not a valid object_id",
+                object_id,
+                name
+            );
+        }
+    }
+
     #[test]
     fn test_performance_common_entities() {
         // Test with common entity patterns
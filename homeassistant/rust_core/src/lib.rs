@@ -9,13 +9,13 @@
 //! as they release the GIL and perform no I/O operations.
 
 use pyo3::prelude::*;
-use pyo3::types::{PyDict, PyString};
+use pyo3::types::{PyDict, PySet, PyString};
 
 mod entity_id;
 mod state_compare;
 
-use entity_id::{is_valid_domain, is_valid_entity_id, split_entity_id_fast};
-use state_compare::compare_attributes;
+use entity_id::{is_valid_domain, is_valid_entity_id, slugify_object_id, split_entity_id_fast};
+use state_compare::{compare_attributes, diff_attributes, hash_attributes};
 
 /// Fast entity ID validation using direct string parsing.
 ///
@@ -47,6 +47,65 @@ fn py_valid_entity_id(entity_id: &str) -> bool {
     is_valid_entity_id(entity_id)
 }
 
+/// Validate a batch of entity IDs with a single GIL release.
+///
+/// Home Assistant frequently validates large collections of entity IDs at
+/// once (state machine bootstrap, service-call target expansion, config
+/// validation). Checking them one at a time crosses the Python/Rust
+/// boundary once per ID; this function crosses it once for the whole batch.
+///
+/// The sequence is materialized into owned strings under the GIL, then the
+/// entire batch is validated inside `py.allow_threads` — reusing
+/// `is_valid_entity_id` over borrowed `&str` slices with no per-item
+/// allocation.
+///
+/// # Arguments
+/// * `entity_ids` - A sequence of entity ID strings
+///
+/// # Returns
+/// * A `list[bool]`, one flag per input in order
+///
+/// # Examples
+/// ```python
+/// from homeassistant.rust_core import valid_entity_ids
+///
+/// assert valid_entity_ids(["light.kitchen", "invalid"]) == [True, False]
+/// ```
+#[pyfunction]
+#[pyo3(name = "valid_entity_ids")]
+fn py_valid_entity_ids(py: Python, entity_ids: Vec<String>) -> Vec<bool> {
+    py.allow_threads(|| entity_ids.iter().map(|id| is_valid_entity_id(id)).collect())
+}
+
+/// Filter a batch of entity IDs down to the valid ones, with a single GIL release.
+///
+/// Like [`py_valid_entity_ids`] but returns only the entity IDs that pass
+/// validation, preserving their original order. Useful when the caller only
+/// cares about the valid subset and not the per-item flags.
+///
+/// # Arguments
+/// * `entity_ids` - A sequence of entity ID strings
+///
+/// # Returns
+/// * A `list[str]` containing the valid entity IDs in input order
+///
+/// # Examples
+/// ```python
+/// from homeassistant.rust_core import filter_valid_entity_ids
+///
+/// assert filter_valid_entity_ids(["light.kitchen", "invalid"]) == ["light.kitchen"]
+/// ```
+#[pyfunction]
+#[pyo3(name = "filter_valid_entity_ids")]
+fn py_filter_valid_entity_ids(py: Python, entity_ids: Vec<String>) -> Vec<String> {
+    py.allow_threads(|| {
+        entity_ids
+            .into_iter()
+            .filter(|id| is_valid_entity_id(id))
+            .collect()
+    })
+}
+
 /// Fast domain validation.
 ///
 /// Validates that a domain name follows Home Assistant naming rules:
@@ -97,6 +156,37 @@ fn py_split_entity_id(py: Python, entity_id: &str) -> PyResult<(&str, &str)> {
     })
 }
 
+/// Slugify a human-friendly name into a valid object_id.
+///
+/// Lowercases ASCII letters, collapses any run of characters outside
+/// `[a-z0-9]` into a single underscore, and trims leading/trailing
+/// underscores. Returns `None` when nothing valid remains, so callers can
+/// fall back to a default object_id.
+///
+/// The work is done on bytes with the GIL released and a single output
+/// allocation, and the result is guaranteed to round-trip through the
+/// entity-ID validator.
+///
+/// # Arguments
+/// * `name` - The human-friendly name to slugify
+///
+/// # Returns
+/// * `str` - The slugified object_id
+/// * `None` - If no valid object_id could be produced
+///
+/// # Examples
+/// ```python
+/// from homeassistant.rust_core import slugify_object_id
+///
+/// assert slugify_object_id("Living Room") == "living_room"
+/// assert slugify_object_id("***") is None
+/// ```
+#[pyfunction]
+#[pyo3(name = "slugify_object_id")]
+fn py_slugify_object_id(py: Python, name: &str) -> Option<String> {
+    py.allow_threads(|| slugify_object_id(name))
+}
+
 /// Fast attribute dictionary comparison with early exit optimization.
 ///
 /// Compares two dictionaries for equality with optimizations:
@@ -142,6 +232,72 @@ fn py_fast_attributes_equal<'py>(
     py.allow_threads(|| compare_attributes(dict1, dict2))
 }
 
+/// Compute which attribute keys changed between two dictionaries.
+///
+/// Returns a `set` of the keys present in exactly one dict plus the keys
+/// whose values differ, or `None` when the dictionaries are equal (the same
+/// cheap fast-path as `fast_attributes_equal`). This lets Home Assistant
+/// compute a minimal state-change delta natively, without a second Python
+/// pass over the attributes.
+///
+/// # Arguments
+/// * `dict1` - The previous attribute dictionary
+/// * `dict2` - The new attribute dictionary
+///
+/// # Returns
+/// * `None` if the dictionaries are equal
+/// * A `set[str]` (or set of whatever key type) of the changed keys otherwise
+///
+/// # Examples
+/// ```python
+/// from homeassistant.rust_core import diff_attributes
+///
+/// old = {"brightness": 255, "color_temp": 370}
+/// new = {"brightness": 200, "color_temp": 370}
+///
+/// assert diff_attributes(old, new) == {"brightness"}
+/// assert diff_attributes(old, old) is None
+/// ```
+#[pyfunction]
+#[pyo3(name = "diff_attributes")]
+fn py_diff_attributes<'py>(
+    dict1: &Bound<'py, PyDict>,
+    dict2: &Bound<'py, PyDict>,
+) -> PyResult<Option<Bound<'py, PySet>>> {
+    diff_attributes(dict1, dict2)
+}
+
+/// Fast order-independent hash of a state attribute dictionary.
+///
+/// Computes a 64-bit AHash over the dictionary's contents, recursing into
+/// nested dicts and sequences. The hash is independent of key iteration
+/// order, so two dicts with equal contents hash equal.
+///
+/// This gives callers a cheap way to detect attribute changes and a compact
+/// cache key: when two cached hashes differ the attribute sets are certainly
+/// different, so `fast_attributes_equal` can be skipped entirely.
+///
+/// # Arguments
+/// * `attributes` - The attribute dictionary to hash
+///
+/// # Returns
+/// * A 64-bit hash of the dictionary's contents
+///
+/// # Examples
+/// ```python
+/// from homeassistant.rust_core import fast_attributes_hash
+///
+/// a = {"brightness": 255, "color_temp": 370}
+/// b = {"color_temp": 370, "brightness": 255}
+///
+/// assert fast_attributes_hash(a) == fast_attributes_hash(b)
+/// ```
+#[pyfunction]
+#[pyo3(name = "fast_attributes_hash")]
+fn py_fast_attributes_hash(attributes: &Bound<'_, PyDict>) -> PyResult<u64> {
+    hash_attributes(attributes)
+}
+
 /// Home Assistant Rust Core Module
 ///
 /// This module provides high-performance implementations of core functions
@@ -149,8 +305,13 @@ fn py_fast_attributes_equal<'py>(
 #[pymodule]
 fn rust_core(m: &Bound<'_, PyModule>) -> PyResult<()> {
     m.add_function(wrap_pyfunction!(py_valid_entity_id, m)?)?;
+    m.add_function(wrap_pyfunction!(py_valid_entity_ids, m)?)?;
+    m.add_function(wrap_pyfunction!(py_filter_valid_entity_ids, m)?)?;
     m.add_function(wrap_pyfunction!(py_valid_domain, m)?)?;
     m.add_function(wrap_pyfunction!(py_split_entity_id, m)?)?;
+    m.add_function(wrap_pyfunction!(py_slugify_object_id, m)?)?;
     m.add_function(wrap_pyfunction!(py_fast_attributes_equal, m)?)?;
+    m.add_function(wrap_pyfunction!(py_fast_attributes_hash, m)?)?;
+    m.add_function(wrap_pyfunction!(py_diff_attributes, m)?)?;
     Ok(())
 }
@@ -3,8 +3,20 @@
 //! This module provides optimized dictionary comparison for Home Assistant
 //! state attributes, which is called on every state update.
 
+use std::hash::Hasher;
+
+use ahash::AHasher;
 use pyo3::prelude::*;
-use pyo3::types::{PyDict, PyAny};
+use pyo3::types::{
+    PyAny, PyBool, PyBytes, PyDict, PyFloat, PyInt, PyList, PySet, PyString, PyTuple,
+};
+
+/// Maximum recursion depth when walking nested containers natively.
+///
+/// Beyond this depth we stop recursing in Rust and defer to Python's
+/// rich comparison. This bounds stack usage and guards against
+/// self-referential (cyclic) containers overflowing the stack.
+const MAX_RECURSION_DEPTH: usize = 10;
 
 /// Compare two Python dictionaries for equality with early exit optimization.
 ///
@@ -37,6 +49,19 @@ use pyo3::types::{PyDict, PyAny};
 pub fn compare_attributes<'py>(
     dict1: &Bound<'py, PyDict>,
     dict2: &Bound<'py, PyDict>,
+) -> PyResult<bool> {
+    compare_dicts(dict1, dict2, 0)
+}
+
+/// Compare two dictionaries at a given recursion depth.
+///
+/// Shared by [`compare_attributes`] (the entry point) and
+/// [`compare_values`] (when it recurses into nested dict values), so the
+/// length + key-lookup + recursive-value logic lives in exactly one place.
+fn compare_dicts<'py>(
+    dict1: &Bound<'py, PyDict>,
+    dict2: &Bound<'py, PyDict>,
+    depth: usize,
 ) -> PyResult<bool> {
     // Fast path: same reference
     if dict1.as_ptr() == dict2.as_ptr() {
@@ -55,16 +80,7 @@ pub fn compare_attributes<'py>(
             return Ok(false);
         };
 
-        // Compare values using Python's rich comparison
-        // This handles all Python types correctly including:
-        // - None
-        // - bools
-        // - ints
-        // - floats
-        // - strings
-        // - nested dicts/lists
-        // - custom objects with __eq__
-        if !compare_values(&value1, &value2)? {
+        if !compare_values_inner(&value1, &value2, depth)? {
             return Ok(false);
         }
     }
@@ -74,9 +90,12 @@ pub fn compare_attributes<'py>(
 
 /// Compare two Python values for equality.
 ///
-/// This is a helper function that uses Python's rich comparison protocol.
-/// It handles all Python types correctly and is faster than calling
-/// Python's __eq__ method directly from Rust.
+/// This walks common Python container and scalar types directly in Rust so
+/// that nested `dict`/`list`/`tuple` attributes (e.g. `supported_features`
+/// maps, `hs_color` lists, forecast arrays) don't bounce back into the
+/// interpreter for every element. Types we don't recognise — custom
+/// objects with `__eq__` — and pairs of different recognised types (e.g. an
+/// `int` against a `float`) fall back to Python's rich comparison.
 ///
 /// # Arguments
 /// * `val1` - First value to compare
@@ -88,15 +107,299 @@ pub fn compare_attributes<'py>(
 /// * `Err` if comparison fails
 #[inline]
 fn compare_values(val1: &Bound<'_, PyAny>, val2: &Bound<'_, PyAny>) -> PyResult<bool> {
+    compare_values_inner(val1, val2, 0)
+}
+
+/// Recursive worker for [`compare_values`], tracking the current depth.
+///
+/// Once `depth` reaches [`MAX_RECURSION_DEPTH`] we stop recursing and defer
+/// to Python's rich comparison, so cyclic or pathologically deep structures
+/// can't overflow the stack.
+fn compare_values_inner(
+    val1: &Bound<'_, PyAny>,
+    val2: &Bound<'_, PyAny>,
+    depth: usize,
+) -> PyResult<bool> {
     // Fast path: same reference
     if val1.as_ptr() == val2.as_ptr() {
         return Ok(true);
     }
 
-    // Use Python's rich comparison (handles all types correctly)
+    // Bail out of the native path once we've recursed too far.
+    if depth >= MAX_RECURSION_DEPTH {
+        return val1.eq(val2);
+    }
+
+    // Lists: compare length then each element pairwise by index.
+    if let (Ok(list1), Ok(list2)) = (val1.downcast::<PyList>(), val2.downcast::<PyList>()) {
+        if list1.len() != list2.len() {
+            return Ok(false);
+        }
+        for (item1, item2) in list1.iter().zip(list2.iter()) {
+            if !compare_values_inner(&item1, &item2, depth + 1)? {
+                return Ok(false);
+            }
+        }
+        return Ok(true);
+    }
+
+    // Tuples: same treatment as lists.
+    if let (Ok(tuple1), Ok(tuple2)) = (val1.downcast::<PyTuple>(), val2.downcast::<PyTuple>()) {
+        if tuple1.len() != tuple2.len() {
+            return Ok(false);
+        }
+        for (item1, item2) in tuple1.iter().zip(tuple2.iter()) {
+            if !compare_values_inner(&item1, &item2, depth + 1)? {
+                return Ok(false);
+            }
+        }
+        return Ok(true);
+    }
+
+    // Dicts: reuse the shared length + key-lookup + recursive-value logic.
+    if let (Ok(dict1), Ok(dict2)) = (val1.downcast::<PyDict>(), val2.downcast::<PyDict>()) {
+        return compare_dicts(dict1, dict2, depth + 1);
+    }
+
+    // None compares equal only to None.
+    if val1.is_none() || val2.is_none() {
+        return Ok(val1.is_none() && val2.is_none());
+    }
+
+    // Scalars: downcast to the concrete type and compare the Rust value.
+    // `bool` is checked before `int` because Python's `bool` is a subclass
+    // of `int`: two bools compare by truth value, while a bool and an int
+    // both take the int path and compare numerically, so `True == 1` as in
+    // Python.
+    if let (Ok(b1), Ok(b2)) = (val1.downcast::<PyBool>(), val2.downcast::<PyBool>()) {
+        return Ok(b1.is_true() == b2.is_true());
+    }
+    if val1.is_instance_of::<PyInt>() && val2.is_instance_of::<PyInt>() {
+        if let (Ok(i1), Ok(i2)) = (val1.extract::<i64>(), val2.extract::<i64>()) {
+            return Ok(i1 == i2);
+        }
+        // Out-of-range integers: let Python compare the big ints.
+        return val1.eq(val2);
+    }
+    if let (Ok(f1), Ok(f2)) = (val1.downcast::<PyFloat>(), val2.downcast::<PyFloat>()) {
+        return Ok(f1.value() == f2.value());
+    }
+    if let (Ok(s1), Ok(s2)) = (val1.downcast::<PyString>(), val2.downcast::<PyString>()) {
+        return Ok(s1.to_str()? == s2.to_str()?);
+    }
+    if let (Ok(b1), Ok(b2)) = (val1.downcast::<PyBytes>(), val2.downcast::<PyBytes>()) {
+        return Ok(b1.as_bytes() == b2.as_bytes());
+    }
+
+    // Unrecognised types (custom objects with __eq__): defer to Python.
     val1.eq(val2)
 }
 
+/// Compute the set of attribute keys that changed between two dictionaries.
+///
+/// Returns the keys present in exactly one dict, plus the keys whose values
+/// differ. When the two dicts are equal it returns `None`, preserving the
+/// cheap fast-path used by [`compare_attributes`] so callers can treat
+/// "no change" without materialising an empty set.
+///
+/// Unlike [`compare_attributes`], which early-exits on the first mismatch,
+/// this makes a single pass over `dict1` accumulating differing keys, then
+/// scans `dict2` for keys absent from `dict1`.
+///
+/// # Arguments
+/// * `dict1` - The previous attribute dictionary
+/// * `dict2` - The new attribute dictionary
+///
+/// # Returns
+/// * `Ok(None)` if the dictionaries are equal
+/// * `Ok(Some(set))` containing the changed keys otherwise
+/// * `Err` if comparison fails
+pub fn diff_attributes<'py>(
+    dict1: &Bound<'py, PyDict>,
+    dict2: &Bound<'py, PyDict>,
+) -> PyResult<Option<Bound<'py, PySet>>> {
+    // Fast path: same reference means nothing changed.
+    if dict1.as_ptr() == dict2.as_ptr() {
+        return Ok(None);
+    }
+
+    let changed = PySet::empty(dict1.py())?;
+
+    // Single pass over dict1: collect keys that are missing from or differ
+    // in dict2.
+    for (key, value1) in dict1.iter() {
+        match dict2.get_item(&key)? {
+            Some(value2) => {
+                if !compare_values(&value1, &value2)? {
+                    changed.add(key)?;
+                }
+            }
+            None => changed.add(key)?,
+        }
+    }
+
+    // Scan dict2 for keys that dict1 never had.
+    for (key, _value) in dict2.iter() {
+        if !dict1.contains(&key)? {
+            changed.add(key)?;
+        }
+    }
+
+    if changed.is_empty() {
+        Ok(None)
+    } else {
+        Ok(Some(changed))
+    }
+}
+
+/// Type tags mixed into per-value hashes so that values of different types
+/// (e.g. the int `1` and the string `"1"`) don't collide.
+mod tag {
+    pub const NONE: u8 = 0;
+    pub const INT: u8 = 2;
+    pub const FLOAT: u8 = 3;
+    pub const STR: u8 = 4;
+    pub const BYTES: u8 = 5;
+    pub const SEQ: u8 = 6;
+    pub const DICT: u8 = 7;
+    pub const FALLBACK: u8 = 8;
+}
+
+/// Compute an order-independent 64-bit AHash over a dictionary's contents.
+///
+/// Each key and value is hashed (recursing into nested dicts and sequences),
+/// the two are combined into a per-entry hash, and the entry hashes are
+/// folded together with a commutative operation so that iteration order does
+/// not affect the result. Two dicts with equal contents therefore hash
+/// equal, and callers can skip [`compare_attributes`] entirely when two
+/// cached hashes differ, or use the hash as a cheap cache key.
+///
+/// Values that can't be hashed natively fall back to Python's `hash()`.
+///
+/// # Arguments
+/// * `dict` - The dictionary to hash
+///
+/// # Returns
+/// * `Ok(hash)` - The combined 64-bit hash
+/// * `Err` if a value's Python `hash()` fallback raises (e.g. unhashable)
+pub fn hash_attributes(dict: &Bound<'_, PyDict>) -> PyResult<u64> {
+    hash_dict(dict, 0)
+}
+
+/// Order-independent hash of a dict's entries at a given recursion depth.
+fn hash_dict(dict: &Bound<'_, PyDict>, depth: usize) -> PyResult<u64> {
+    let mut combined: u64 = 0;
+    for (key, value) in dict.iter() {
+        let entry = mix(hash_value(&key, depth)?, hash_value(&value, depth)?);
+        // Commutative fold: iteration order doesn't change the result.
+        combined = combined.wrapping_add(entry);
+    }
+    Ok(combined)
+}
+
+/// Hash a single Python value, recursing into nested containers.
+///
+/// Mirrors the type dispatch of [`compare_values`] so that values which
+/// compare equal also hash equal. Beyond [`MAX_RECURSION_DEPTH`], or for
+/// types we don't recognise, it defers to Python's `hash()`.
+fn hash_value(val: &Bound<'_, PyAny>, depth: usize) -> PyResult<u64> {
+    if depth >= MAX_RECURSION_DEPTH {
+        return hash_fallback(val);
+    }
+
+    if val.is_none() {
+        return Ok(tagged(tag::NONE, 0));
+    }
+    // Numeric values compare equal across types — Python treats
+    // `True == 1 == 1.0` and `20 == 20.0`, and [`compare_values`] follows
+    // suit — so every integer-valued number (bool, int, integral float) must
+    // hash to the same tagged payload to preserve the equal⟹hash-equal
+    // invariant callers rely on. `bool` is handled before `int` as in the
+    // comparison path, folded into the int payload.
+    if let Ok(b) = val.downcast::<PyBool>() {
+        return Ok(tagged(tag::INT, b.is_true() as u64));
+    }
+    if val.is_instance_of::<PyInt>() {
+        if let Ok(i) = val.extract::<i64>() {
+            return Ok(tagged(tag::INT, i as u64));
+        }
+        // Out-of-range integers: let Python hash the big int.
+        return hash_fallback(val);
+    }
+    if let Ok(f) = val.downcast::<PyFloat>() {
+        let v = f.value();
+        // An integer-valued float equals the same-valued int, so route it
+        // through the int payload; other floats hash by bit pattern. Floats
+        // outside `i64` range defer to Python's `hash()`, matching the
+        // out-of-range integer path so equal extremes still hash equal.
+        if v.fract() == 0.0 {
+            if v >= i64::MIN as f64 && v < i64::MAX as f64 {
+                return Ok(tagged(tag::INT, v as i64 as u64));
+            }
+            return hash_fallback(val);
+        }
+        return Ok(tagged(tag::FLOAT, v.to_bits()));
+    }
+    if let Ok(s) = val.downcast::<PyString>() {
+        let mut hasher = AHasher::default();
+        hasher.write_u8(tag::STR);
+        hasher.write(s.to_str()?.as_bytes());
+        return Ok(hasher.finish());
+    }
+    if let Ok(b) = val.downcast::<PyBytes>() {
+        let mut hasher = AHasher::default();
+        hasher.write_u8(tag::BYTES);
+        hasher.write(b.as_bytes());
+        return Ok(hasher.finish());
+    }
+    // Lists and tuples hash the same way: order matters for sequences.
+    if let Ok(list) = val.downcast::<PyList>() {
+        let mut hasher = AHasher::default();
+        hasher.write_u8(tag::SEQ);
+        for item in list.iter() {
+            hasher.write_u64(hash_value(&item, depth + 1)?);
+        }
+        return Ok(hasher.finish());
+    }
+    if let Ok(tuple) = val.downcast::<PyTuple>() {
+        let mut hasher = AHasher::default();
+        hasher.write_u8(tag::SEQ);
+        for item in tuple.iter() {
+            hasher.write_u64(hash_value(&item, depth + 1)?);
+        }
+        return Ok(hasher.finish());
+    }
+    if let Ok(dict) = val.downcast::<PyDict>() {
+        return Ok(mix(u64::from(tag::DICT), hash_dict(dict, depth + 1)?));
+    }
+
+    hash_fallback(val)
+}
+
+/// Fall back to Python's `hash()` for a value we can't hash natively.
+#[inline]
+fn hash_fallback(val: &Bound<'_, PyAny>) -> PyResult<u64> {
+    Ok(tagged(tag::FALLBACK, val.hash()? as u64))
+}
+
+/// AHash of a type tag combined with a single 64-bit payload.
+#[inline]
+fn tagged(tag: u8, value: u64) -> u64 {
+    let mut hasher = AHasher::default();
+    hasher.write_u8(tag);
+    hasher.write_u64(value);
+    hasher.finish()
+}
+
+/// Combine two 64-bit hashes into one (used for key/value pairs).
+#[inline]
+fn mix(a: u64, b: u64) -> u64 {
+    let mut hasher = AHasher::default();
+    hasher.write_u64(a);
+    hasher.write_u64(b);
+    hasher.finish()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -161,4 +464,194 @@ mod tests {
             assert!(compare_attributes(&dict1, &dict2).unwrap());
         });
     }
+
+    #[test]
+    fn test_equal_nested_lists() {
+        Python::with_gil(|py| {
+            let dict1 = [("hs_color", vec![30, 70])].into_py_dict(py).unwrap();
+            let dict2 = [("hs_color", vec![30, 70])].into_py_dict(py).unwrap();
+            assert!(compare_attributes(&dict1, &dict2).unwrap());
+        });
+    }
+
+    #[test]
+    fn test_different_nested_lists() {
+        Python::with_gil(|py| {
+            let dict1 = [("hs_color", vec![30, 70])].into_py_dict(py).unwrap();
+            let dict2 = [("hs_color", vec![30, 71])].into_py_dict(py).unwrap();
+            assert!(!compare_attributes(&dict1, &dict2).unwrap());
+        });
+    }
+
+    #[test]
+    fn test_different_nested_list_length() {
+        Python::with_gil(|py| {
+            let dict1 = [("hs_color", vec![30, 70])].into_py_dict(py).unwrap();
+            let dict2 = [("hs_color", vec![30, 70, 0])].into_py_dict(py).unwrap();
+            assert!(!compare_attributes(&dict1, &dict2).unwrap());
+        });
+    }
+
+    #[test]
+    fn test_equal_nested_dicts() {
+        Python::with_gil(|py| {
+            let inner1 = [("min", 1), ("max", 5)].into_py_dict(py).unwrap();
+            let inner2 = [("min", 1), ("max", 5)].into_py_dict(py).unwrap();
+            let dict1 = [("range", inner1)].into_py_dict(py).unwrap();
+            let dict2 = [("range", inner2)].into_py_dict(py).unwrap();
+            assert!(compare_attributes(&dict1, &dict2).unwrap());
+        });
+    }
+
+    #[test]
+    fn test_different_nested_dicts() {
+        Python::with_gil(|py| {
+            let inner1 = [("min", 1), ("max", 5)].into_py_dict(py).unwrap();
+            let inner2 = [("min", 1), ("max", 6)].into_py_dict(py).unwrap();
+            let dict1 = [("range", inner1)].into_py_dict(py).unwrap();
+            let dict2 = [("range", inner2)].into_py_dict(py).unwrap();
+            assert!(!compare_attributes(&dict1, &dict2).unwrap());
+        });
+    }
+
+    #[test]
+    fn test_diff_equal_dicts_returns_none() {
+        Python::with_gil(|py| {
+            let dict1 = [("a", 1), ("b", 2)].into_py_dict(py).unwrap();
+            let dict2 = [("a", 1), ("b", 2)].into_py_dict(py).unwrap();
+            assert!(diff_attributes(&dict1, &dict2).unwrap().is_none());
+        });
+    }
+
+    #[test]
+    fn test_diff_same_reference_returns_none() {
+        Python::with_gil(|py| {
+            let dict = [("a", 1)].into_py_dict(py).unwrap();
+            assert!(diff_attributes(&dict, &dict).unwrap().is_none());
+        });
+    }
+
+    #[test]
+    fn test_diff_changed_value() {
+        Python::with_gil(|py| {
+            let dict1 = [("a", 1), ("b", 2)].into_py_dict(py).unwrap();
+            let dict2 = [("a", 1), ("b", 3)].into_py_dict(py).unwrap();
+            let changed = diff_attributes(&dict1, &dict2).unwrap().unwrap();
+            assert_eq!(changed.len(), 1);
+            assert!(changed.contains("b").unwrap());
+        });
+    }
+
+    #[test]
+    fn test_diff_added_and_removed_keys() {
+        Python::with_gil(|py| {
+            let dict1 = [("a", 1), ("b", 2)].into_py_dict(py).unwrap();
+            let dict2 = [("a", 1), ("c", 3)].into_py_dict(py).unwrap();
+            let changed = diff_attributes(&dict1, &dict2).unwrap().unwrap();
+            assert_eq!(changed.len(), 2);
+            assert!(changed.contains("b").unwrap());
+            assert!(changed.contains("c").unwrap());
+        });
+    }
+
+    #[test]
+    fn test_hash_equal_dicts_match() {
+        Python::with_gil(|py| {
+            let dict1 = [("brightness", 255), ("color_temp", 370)]
+                .into_py_dict(py)
+                .unwrap();
+            let dict2 = [("brightness", 255), ("color_temp", 370)]
+                .into_py_dict(py)
+                .unwrap();
+            assert_eq!(
+                hash_attributes(&dict1).unwrap(),
+                hash_attributes(&dict2).unwrap()
+            );
+        });
+    }
+
+    #[test]
+    fn test_hash_order_independent() {
+        Python::with_gil(|py| {
+            let dict1 = [("a", 1), ("b", 2)].into_py_dict(py).unwrap();
+            let dict2 = [("b", 2), ("a", 1)].into_py_dict(py).unwrap();
+            assert_eq!(
+                hash_attributes(&dict1).unwrap(),
+                hash_attributes(&dict2).unwrap()
+            );
+        });
+    }
+
+    #[test]
+    fn test_hash_different_values_differ() {
+        Python::with_gil(|py| {
+            let dict1 = [("brightness", 255)].into_py_dict(py).unwrap();
+            let dict2 = [("brightness", 200)].into_py_dict(py).unwrap();
+            assert_ne!(
+                hash_attributes(&dict1).unwrap(),
+                hash_attributes(&dict2).unwrap()
+            );
+        });
+    }
+
+    #[test]
+    fn test_hash_nested_values() {
+        Python::with_gil(|py| {
+            let dict1 = [("hs_color", vec![30, 70])].into_py_dict(py).unwrap();
+            let dict2 = [("hs_color", vec![30, 70])].into_py_dict(py).unwrap();
+            let dict3 = [("hs_color", vec![30, 71])].into_py_dict(py).unwrap();
+            assert_eq!(
+                hash_attributes(&dict1).unwrap(),
+                hash_attributes(&dict2).unwrap()
+            );
+            assert_ne!(
+                hash_attributes(&dict1).unwrap(),
+                hash_attributes(&dict3).unwrap()
+            );
+        });
+    }
+
+    #[test]
+    fn test_hash_numeric_cross_type_equal() {
+        Python::with_gil(|py| {
+            // `20 == 20.0` and `True == 1` compare equal, so they must hash
+            // equal for the "skip equality when hashes differ" shortcut to be
+            // sound.
+            let int_dict = [("current_temperature", 20)].into_py_dict(py).unwrap();
+            let float_dict = [("current_temperature", 20.0)].into_py_dict(py).unwrap();
+            assert!(compare_attributes(&int_dict, &float_dict).unwrap());
+            assert_eq!(
+                hash_attributes(&int_dict).unwrap(),
+                hash_attributes(&float_dict).unwrap()
+            );
+
+            let bool_dict = [("on", true)].into_py_dict(py).unwrap();
+            let one_dict = [("on", 1)].into_py_dict(py).unwrap();
+            assert_eq!(
+                hash_attributes(&bool_dict).unwrap(),
+                hash_attributes(&one_dict).unwrap()
+            );
+
+            // A non-integral float stays distinct from any int.
+            let half_dict = [("current_temperature", 20.5)].into_py_dict(py).unwrap();
+            assert_ne!(
+                hash_attributes(&int_dict).unwrap(),
+                hash_attributes(&half_dict).unwrap()
+            );
+        });
+    }
+
+    #[test]
+    fn test_bool_compares_equal_to_matching_int() {
+        Python::with_gil(|py| {
+            // Python treats True == 1, so these dicts compare equal.
+            let dict1 = [("on", true)].into_py_dict(py).unwrap();
+            let dict2 = [("on", 1)].into_py_dict(py).unwrap();
+            assert!(compare_attributes(&dict1, &dict2).unwrap());
+
+            // But True != 2.
+            let dict3 = [("on", 2)].into_py_dict(py).unwrap();
+            assert!(!compare_attributes(&dict1, &dict3).unwrap());
+        });
+    }
 }